@@ -1,17 +1,64 @@
 use eva_common::prelude::*;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 use std::str::FromStr;
 
-// TODO nums are not fully supported for prod (no parsing/as string)
-#[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+/// Tag identifier: either a string or a numeric id (e.g. a Modbus register
+/// number).
+///
+/// The derived `Ord`/`BTreeMap` iteration order follows declaration order,
+/// then value: all `Str` ids sort before all `Num` ids, and within each
+/// variant ids sort by their natural (lexical or numeric) order.
+#[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Ord, Serialize)]
 #[serde(untagged)]
 pub enum TagId {
     Str(String),
     Num(u64),
 }
 
+impl<'de> Deserialize<'de> for TagId {
+    // A plain `#[derive(Deserialize)]` with `#[serde(untagged)]` would try
+    // `Str` first and always win for string-keyed formats (e.g. JSON object
+    // keys, which are always strings on the wire), silently turning every
+    // numeric id back into a `Str` on round-trip. Parse numeric strings back
+    // into `Num` ourselves, the same way `parse_tag_id`/`FromStr for Tag` do.
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct TagIdVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for TagIdVisitor {
+            type Value = TagId;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a tag id (string or integer)")
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<TagId, E> {
+                Ok(TagId::Num(v))
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<TagId, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(parse_tag_id(v))
+            }
+
+            fn visit_string<E>(self, v: String) -> Result<TagId, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(parse_tag_id(&v))
+            }
+        }
+
+        deserializer.deserialize_any(TagIdVisitor)
+    }
+}
+
 impl TagId {
     pub fn as_str(&self) -> Option<&str> {
         match self {
@@ -19,6 +66,14 @@ impl TagId {
             TagId::Num(_) => None,
         }
     }
+    /// Returns this tag id as a string, borrowing when possible and
+    /// materializing an owned string for numeric ids.
+    pub fn to_cow_str(&self) -> std::borrow::Cow<'_, str> {
+        match self {
+            TagId::Str(v) => std::borrow::Cow::Borrowed(v.as_str()),
+            TagId::Num(v) => std::borrow::Cow::Owned(v.to_string()),
+        }
+    }
 }
 
 impl fmt::Display for TagId {
@@ -31,16 +86,20 @@ impl fmt::Display for TagId {
 }
 
 impl From<&str> for TagId {
+    /// Parses `s` the same way `FromStr for Tag` does: a pure integer within
+    /// `u64` range becomes `Num`, everything else stays `Str`. This keeps
+    /// `TagId::from("1234")` and `"1234".parse::<Tag>()` producing the same
+    /// `BTreeMap` key.
     #[inline]
     fn from(s: &str) -> Self {
-        Self::Str(s.to_owned())
+        parse_tag_id(s)
     }
 }
 
 impl From<String> for TagId {
     #[inline]
     fn from(s: String) -> Self {
-        Self::Str(s)
+        parse_tag_id(&s)
     }
 }
 
@@ -51,16 +110,40 @@ impl From<u64> for TagId {
     }
 }
 
+/// An index range into a `Value::Seq`.
+///
+/// Bounds are signed so a negative offset counts from the end of the
+/// sequence, Python-style (`-1` is the last element). Resolution against a
+/// concrete sequence length happens lazily in `TagMap::get`/`TagMap::set`,
+/// since the length isn't known until then.
 #[derive(Debug, Clone, Eq, PartialEq, Default)]
 pub struct Range {
-    from: Option<usize>,
-    to: Option<usize>,
+    from: Option<isize>,
+    to: Option<isize>,
 }
 
 impl Range {
-    pub fn new(from: Option<usize>, to: Option<usize>) -> Self {
+    pub fn new(from: Option<isize>, to: Option<isize>) -> Self {
         Self { from, to }
     }
+    fn resolve_idx(i: isize, n: usize) -> usize {
+        if i < 0 {
+            usize::try_from(i + n as isize).unwrap_or(0)
+        } else {
+            usize::try_from(i).unwrap_or(0)
+        }
+    }
+    /// Resolves the (possibly negative) start offset against a concrete
+    /// sequence length `n`. Resolved indices below 0 clamp to 0.
+    fn resolved_from(&self, n: usize) -> usize {
+        self.from.map_or(0, |i| Self::resolve_idx(i, n))
+    }
+    /// Resolves the (possibly negative) end offset against a concrete
+    /// sequence length `n`. `None` means open-ended (the caller decides what
+    /// "to the end" means in context).
+    fn resolved_to(&self, n: usize) -> Option<usize> {
+        self.to.map(|i| Self::resolve_idx(i, n))
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -74,14 +157,9 @@ impl fmt::Display for Tag {
         write!(f, "{}", self.id)?;
         if self.has_range() {
             write!(f, "[")?;
-            let mut written = false;
-            if let Some(len) = self.range_len() {
-                if len == 1 {
-                    write!(f, "{}", self.range.from.unwrap_or_default())?;
-                    written = true;
-                }
-            }
-            if !written {
+            if self.is_single() {
+                write!(f, "{}", self.range.from.unwrap_or_default())?;
+            } else {
                 if let Some(from) = self.range.from {
                     write!(f, "{}", from)?;
                 }
@@ -127,49 +205,121 @@ impl Tag {
     pub fn has_range(&self) -> bool {
         self.range.from.is_some() || self.range.to.is_some()
     }
-    pub fn range_len(&self) -> Option<usize> {
-        self.range
-            .to
-            .map(|to| to - self.range.from.unwrap_or_default() + 1)
+    /// Whether this range selects a single element (`from == to`).
+    fn is_single(&self) -> bool {
+        self.range.from.is_some() && self.range.from == self.range.to
+    }
+    /// Resolves the range's start offset against a concrete sequence length.
+    fn resolved_from(&self, n: usize) -> usize {
+        self.range.resolved_from(n)
+    }
+    /// Resolves the range's end offset against a concrete sequence length.
+    fn resolved_to(&self, n: usize) -> Option<usize> {
+        self.range.resolved_to(n)
+    }
+    /// Resolves both ends of the range against a concrete sequence length,
+    /// erroring if the resolved bounds violate `from <= to` (this can only
+    /// be checked once both offsets are resolved, since a mixed-sign range
+    /// like `-1-3` may resolve either way depending on `n`).
+    fn resolved_range(&self, n: usize) -> EResult<(usize, Option<usize>)> {
+        let from = self.resolved_from(n);
+        let to = self.resolved_to(n);
+        if let Some(to) = to {
+            if from > to {
+                return Err(Error::invalid_params("invalid seq index"));
+            }
+        }
+        Ok((from, to))
     }
 }
 
 fn parse_range(s: &str) -> EResult<Range> {
-    if let Some(pos) = s.find('-') {
+    if s == "-" {
+        return Ok(Range::new(None, None));
+    }
+    // skip a leading sign so it isn't mistaken for the from/to separator
+    let search_start = usize::from(s.starts_with('-'));
+    if let Some(rel_pos) = s[search_start..].find('-') {
+        let pos = search_start + rel_pos;
         let f = &s[..pos];
         let t = &s[pos + 1..];
         let from = if f.is_empty() { None } else { Some(f.parse()?) };
         let to = if t.is_empty() { None } else { Some(t.parse()?) };
-        if let Some(f) = from {
-            if let Some(t) = to {
-                if f > t {
-                    return Err(Error::invalid_params("invalid seq index"));
-                }
-            }
-        }
+        // `from`/`to` may have mixed signs (e.g. `-1-3`), so whether `from <=
+        // to` holds can only be decided once both are resolved against a
+        // concrete sequence length - see `Tag::resolved_range`.
         Ok(Range::new(from, to))
     } else {
-        let n: usize = s.parse()?;
+        let n: isize = s.parse()?;
         Ok(Range::new(Some(n), Some(n)))
     }
 }
 
+/// Parses a tag id base (the part before any `[...]` range suffix): pure
+/// integers within `u64` range become `TagId::Num`, everything else stays
+/// `TagId::Str`.
+fn parse_tag_id(s: &str) -> TagId {
+    s.parse::<u64>().map_or_else(|_| TagId::Str(s.to_owned()), TagId::Num)
+}
+
+/// Parses a `+`-joined flag string (bitflags-style), empty string for none.
+fn parse_flags(s: &str) -> BTreeSet<String> {
+    if s.is_empty() {
+        BTreeSet::new()
+    } else {
+        s.split('+').map(ToOwned::to_owned).collect()
+    }
+}
+
+/// Canonicalizes a flag set back to its sorted `+`-joined string form.
+fn join_flags(flags: &BTreeSet<String>) -> String {
+    flags.iter().cloned().collect::<Vec<_>>().join("+")
+}
+
 impl FromStr for Tag {
     type Err = Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if let Some(x) = s.strip_suffix(']') {
             if let Some(pos) = x.rfind('[') {
                 let range = parse_range(&x[pos + 1..])?;
-                Ok(Tag::new(x[..pos].into(), range))
+                Ok(Tag::new(parse_tag_id(&x[..pos]), range))
             } else {
                 Err(Error::invalid_params("invalid array"))
             }
         } else {
-            Ok(Tag::new0(s.into()))
+            Ok(Tag::new0(parse_tag_id(s)))
         }
     }
 }
 
+/// Tag merge mode, modeled on GStreamer's `GstTagMergeMode`.
+///
+/// Controls how a tag value being written combines with a value already
+/// stored at the same `TagId`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum MergeMode {
+    /// Clear the target first, then insert everything from the source.
+    /// Only `TagMap::merge` clears; `TagMap::set_with`, which writes a
+    /// single tag, treats this like `Replace`.
+    ReplaceAll,
+    /// Overwrite the existing value unconditionally (the previous default
+    /// behavior of `TagMap::set`).
+    Replace,
+    /// Concatenate: existing elements first, then the incoming ones. If
+    /// either the existing or the incoming value is not a seq, behaves like
+    /// `Replace`.
+    Append,
+    /// Concatenate: incoming elements first, then the existing ones. If
+    /// either the existing or the incoming value is not a seq, behaves like
+    /// `Keep`.
+    Prepend,
+    /// Insert only if the tag is not already present.
+    Keep,
+    /// Like `Keep`, but for a seq value appends only the elements beyond
+    /// the length already stored.
+    KeepAll,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct TagMap {
     tags: BTreeMap<TagId, Value>,
@@ -214,16 +364,14 @@ impl TagMap {
         if let Some(val) = self.tags.get(&tag.id) {
             if tag.has_range() {
                 if let Value::Seq(seq) = val {
-                    if let Some(len) = tag.range_len() {
-                        if len == 1 {
-                            return Ok(seq
-                                .get(tag.range.from.unwrap_or_default())
-                                .map_or(Value::Unit, Clone::clone));
-                        }
+                    let n = seq.len();
+                    if tag.is_single() {
+                        let from = tag.resolved_from(n);
+                        return Ok(seq.get(from).map_or(Value::Unit, Clone::clone));
                     }
-                    let from = tag.range.from.unwrap_or_default();
-                    let to = tag.range.to.map_or_else(|| seq.len(), |v| v + 1);
-                    let mut result = Vec::with_capacity(to - from + 1);
+                    let (from, to_opt) = tag.resolved_range(n)?;
+                    let to = to_opt.map_or(n, |v| v + 1);
+                    let mut result = Vec::with_capacity(to.saturating_sub(from));
                     for i in from..to {
                         result.push(seq.get(i).map_or(Value::Unit, Clone::clone));
                     }
@@ -243,67 +391,68 @@ impl TagMap {
             if let Some(val) = self.tags.get_mut(&tag.id) {
                 // setting existing array tag
                 if let Value::Seq(seq) = val {
-                    if let Some(len) = tag.range_len() {
-                        if len == 1 {
-                            // replace a single el
-                            let idx = tag.range.from.unwrap_or_default();
-                            if seq.len() < idx + 1 {
-                                seq.resize(idx + 1, Value::Unit);
+                    let n = seq.len();
+                    if tag.is_single() {
+                        // replace a single el
+                        let from = tag.resolved_from(n);
+                        if seq.len() < from + 1 {
+                            seq.resize(from + 1, Value::Unit);
+                        }
+                        seq[from] = value;
+                    } else {
+                        let (from, to_opt) = tag.resolved_range(n)?;
+                        if let Some(to) = to_opt {
+                            if let Value::Seq(s) = value {
+                                let len = to.saturating_sub(from) + 1;
+                                if s.len() != len {
+                                    return Err(Error::invalid_params("invalid value seq len"));
+                                }
+                                // set array part
+                                let last_idx = to + 1;
+                                let tail = if last_idx > seq.len() {
+                                    None
+                                } else {
+                                    Some(seq.split_off(last_idx))
+                                };
+                                seq.resize(from, Value::Unit);
+                                seq.extend(s);
+                                if let Some(t) = tail {
+                                    seq.extend(t);
+                                }
+                            } else {
+                                return Err(Error::invalid_params("value is not a seq"));
                             }
-                            seq[idx] = value;
                         } else if let Value::Seq(s) = value {
-                            if s.len() != len {
-                                return Err(Error::invalid_params("invalid value seq len"));
-                            }
-                            // set array part
-                            let last_idx = tag.range.to.unwrap_or_default() + 1;
-                            let tail = if last_idx > seq.len() {
-                                None
-                            } else {
-                                Some(seq.split_off(last_idx))
-                            };
-                            let first_idx = tag.range.from.unwrap_or_default();
-                            seq.resize(first_idx, Value::Unit);
+                            // no end given - starting index only, replace the tail entirely
+                            seq.resize(from, Value::Unit);
                             seq.extend(s);
-                            if let Some(t) = tail {
-                                seq.extend(t);
-                            }
                         } else {
                             return Err(Error::invalid_params("value is not a seq"));
                         }
-                    } else if let Value::Seq(s) = value {
-                        // no len given - we have starting index only
-                        let idx = tag.range.from.unwrap_or_default();
-                        seq.resize(idx, Value::Unit);
-                        seq.extend(s);
-                    } else {
-                        return Err(Error::invalid_params("value is not a seq"));
                     }
                 } else {
                     return Err(Error::invalid_params("tag is not an array"));
                 }
             } else if let Value::Seq(seq) = value {
-                let len = if let Some(len) = tag.range_len() {
+                let (from, to_opt) = tag.resolved_range(0)?;
+                let len = if let Some(to) = to_opt {
+                    let len = to.saturating_sub(from) + 1;
                     if len != seq.len() {
                         return Err(Error::invalid_params("invalid value seq len"));
                     }
                     len
                 } else {
-                    tag.range.from.unwrap_or_default() + seq.len()
+                    from + seq.len()
                 };
                 let mut result = Vec::with_capacity(len);
-                result.resize(tag.range.from.unwrap_or_default(), Value::Unit);
+                result.resize(from, Value::Unit);
                 result.extend(seq);
                 self.tags.insert(tag.id, Value::Seq(result));
-            } else if let Some(len) = tag.range_len() {
-                if len == 1 {
-                    let idx = tag.range.from.unwrap_or_default();
-                    let mut result = vec![Value::Unit; idx + 1];
-                    result[idx] = value;
-                    self.tags.insert(tag.id, Value::Seq(result));
-                } else {
-                    return Err(Error::invalid_params("value is not a seq"));
-                }
+            } else if tag.is_single() {
+                let idx = tag.resolved_from(0);
+                let mut result = vec![Value::Unit; idx + 1];
+                result[idx] = value;
+                self.tags.insert(tag.id, Value::Seq(result));
             } else {
                 return Err(Error::invalid_params("value is not a seq"));
             }
@@ -312,11 +461,202 @@ impl TagMap {
         }
         Ok(())
     }
+    /// Sets a single tag's value, combining it with whatever is already
+    /// stored at `tag` according to `mode`.
+    pub fn set_with(&mut self, tag: Tag, value: Value, mode: MergeMode) -> EResult<()> {
+        match mode {
+            MergeMode::ReplaceAll | MergeMode::Replace => self.set(tag, value),
+            MergeMode::Keep => {
+                if self.tags.contains_key(&tag.id) {
+                    Ok(())
+                } else {
+                    self.set(tag, value)
+                }
+            }
+            MergeMode::KeepAll => {
+                let existing_len = match self.tags.get(&tag.id) {
+                    Some(Value::Seq(s)) => Some(s.len()),
+                    Some(_) => return Ok(()),
+                    None => None,
+                };
+                if let Some(existing_len) = existing_len {
+                    if let Value::Seq(incoming) = value {
+                        if let Some(Value::Seq(existing)) = self.tags.get_mut(&tag.id) {
+                            if incoming.len() > existing_len {
+                                existing.extend(incoming.into_iter().skip(existing_len));
+                            }
+                        }
+                    }
+                    Ok(())
+                } else {
+                    self.set(tag, value)
+                }
+            }
+            MergeMode::Append => {
+                let existing_is_seq = matches!(self.tags.get(&tag.id), Some(Value::Seq(_)));
+                let incoming_is_seq = matches!(&value, Value::Seq(_));
+                if existing_is_seq && incoming_is_seq {
+                    if let (Some(Value::Seq(existing)), Value::Seq(incoming)) =
+                        (self.tags.get_mut(&tag.id), value)
+                    {
+                        existing.extend(incoming);
+                    }
+                    Ok(())
+                } else if self.tags.contains_key(&tag.id) {
+                    self.set_with(tag, value, MergeMode::Replace)
+                } else {
+                    self.set(tag, value)
+                }
+            }
+            MergeMode::Prepend => {
+                let existing_is_seq = matches!(self.tags.get(&tag.id), Some(Value::Seq(_)));
+                let incoming_is_seq = matches!(&value, Value::Seq(_));
+                if existing_is_seq && incoming_is_seq {
+                    if let (Some(Value::Seq(existing)), Value::Seq(mut incoming)) =
+                        (self.tags.get_mut(&tag.id), value)
+                    {
+                        incoming.append(existing);
+                        *existing = incoming;
+                    }
+                    Ok(())
+                } else if self.tags.contains_key(&tag.id) {
+                    self.set_with(tag, value, MergeMode::Keep)
+                } else {
+                    self.set(tag, value)
+                }
+            }
+        }
+    }
+    /// Merges all tags from `other` into `self` according to `mode`.
+    pub fn merge(&mut self, other: &TagMap, mode: MergeMode) -> EResult<()> {
+        if mode == MergeMode::ReplaceAll {
+            self.tags.clear();
+        }
+        let per_tag_mode = if mode == MergeMode::ReplaceAll {
+            MergeMode::Replace
+        } else {
+            mode
+        };
+        for (id, value) in &other.tags {
+            self.set_with(Tag::new0(id.clone()), value.clone(), per_tag_mode)?;
+        }
+        Ok(())
+    }
+    /// Reads a tag's value as an unordered set of string flags, accepting
+    /// either a `+`-joined `Value::String` (bitflags-style, e.g. `"a+b+c"`,
+    /// empty string for none) or a `Value::Seq` of strings.
+    pub fn get_flags(&self, tag: &Tag) -> EResult<BTreeSet<String>> {
+        match self.tags.get(&tag.id) {
+            Some(Value::String(s)) => Ok(parse_flags(s)),
+            Some(Value::Seq(seq)) => {
+                let mut flags = BTreeSet::new();
+                for v in seq {
+                    if let Value::String(s) = v {
+                        flags.insert(s.clone());
+                    } else {
+                        return Err(Error::invalid_data("flag value is not a string"));
+                    }
+                }
+                Ok(flags)
+            }
+            Some(_) => Err(Error::invalid_data("tag is not a flag set")),
+            None => Err(Error::not_found("no such tag")),
+        }
+    }
+    /// Writes a set of string flags, canonicalized to a sorted `+`-joined
+    /// string (empty string for an empty set).
+    pub fn set_flags(&mut self, tag: Tag, flags: &BTreeSet<String>) -> EResult<()> {
+        self.set(tag, Value::String(join_flags(flags)))
+    }
+    /// Adds a single flag to a tag's flag set, leaving the rest untouched.
+    pub fn add_flag(&mut self, tag: Tag, flag: &str) -> EResult<()> {
+        let mut flags = match self.get_flags(&tag) {
+            Ok(flags) => flags,
+            Err(e) if e.kind() == ErrorKind::ResourceNotFound => BTreeSet::new(),
+            Err(e) => return Err(e),
+        };
+        flags.insert(flag.to_owned());
+        self.set_flags(tag, &flags)
+    }
+    /// Removes a single flag from a tag's flag set, leaving the rest
+    /// untouched.
+    pub fn remove_flag(&mut self, tag: Tag, flag: &str) -> EResult<()> {
+        let mut flags = self.get_flags(&tag)?;
+        flags.remove(flag);
+        self.set_flags(tag, &flags)
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use super::Tag;
+    use super::{Tag, TagId, TagMap, Value};
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_flags() {
+        let mut map = TagMap::default();
+        let tag: Tag = "flags".parse().unwrap();
+
+        map.set(tag.clone(), Value::String("b+a+a".to_owned()))
+            .unwrap();
+        let flags = map.get_flags(&tag).unwrap();
+        assert_eq!(flags, BTreeSet::from(["a".to_owned(), "b".to_owned()]));
+
+        map.add_flag(tag.clone(), "c").unwrap();
+        assert_eq!(map.get(&tag).unwrap(), Value::String("a+b+c".to_owned()));
+
+        map.remove_flag(tag.clone(), "a").unwrap();
+        assert_eq!(map.get(&tag).unwrap(), Value::String("b+c".to_owned()));
+
+        map.remove_flag(tag.clone(), "b").unwrap();
+        map.remove_flag(tag.clone(), "c").unwrap();
+        assert_eq!(map.get(&tag).unwrap(), Value::String(String::new()));
+        assert!(map.get_flags(&tag).unwrap().is_empty());
+
+        let num_tag: Tag = "n".parse().unwrap();
+        map.set(num_tag.clone(), Value::U64(42)).unwrap();
+        assert!(map.add_flag(num_tag.clone(), "c").is_err());
+        assert_eq!(map.get(&num_tag).unwrap(), Value::U64(42));
+    }
+
+    #[test]
+    fn test_numeric_tag_id() {
+        let tag: Tag = "1234".parse().unwrap();
+        assert_eq!(tag.to_string(), "1234");
+
+        let tag: Tag = "1234[0-5]".parse().unwrap();
+        assert_eq!(tag.to_string(), "1234[0-5]");
+
+        let tag: Tag = "not-a-number".parse().unwrap();
+        assert_eq!(tag.to_string(), "not-a-number");
+
+        assert_eq!(TagId::Num(42).to_cow_str(), "42");
+        assert_eq!(TagId::Str("foo".into()).to_cow_str(), "foo");
+        assert_eq!(TagId::Num(42).as_str(), None);
+
+        // `From<&str>`/`From<String>` must agree with `FromStr for Tag` so a
+        // tag looked up via one construction path is visible via the other.
+        assert_eq!(TagId::from("1234"), TagId::Num(1234));
+        assert_eq!(TagId::from("1234".to_owned()), TagId::Num(1234));
+        assert_eq!(Tag::from("1234"), "1234".parse::<Tag>().unwrap());
+    }
+
+    #[test]
+    fn test_numeric_tag_id_map_roundtrip() {
+        let mut map = TagMap::default();
+        map.set("1234".parse().unwrap(), Value::from(42)).unwrap();
+        map.set("str_tag".parse().unwrap(), Value::from(1))
+            .unwrap();
+
+        let json = serde_json::to_string(&map).unwrap();
+        let mut restored: TagMap = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.tags(), map.tags());
+        assert_eq!(
+            restored.get(&"1234".parse().unwrap()).unwrap(),
+            Value::from(42)
+        );
+    }
 
     #[test]
     fn test_parse_display() {
@@ -334,5 +674,46 @@ mod test {
 
         let tag: Tag = "test[1-5]".parse().unwrap();
         assert_eq!(tag.to_string(), "test[1-5]");
+
+        let tag: Tag = "test[-3-]".parse().unwrap();
+        assert_eq!(tag.to_string(), "test[-3-]");
+
+        let tag: Tag = "test[-3--1]".parse().unwrap();
+        assert_eq!(tag.to_string(), "test[-3--1]");
+    }
+
+    #[test]
+    fn test_negative_range_get_set() {
+        let mut map = TagMap::default();
+        let seq: Vec<Value> = vec![0.into(), 1.into(), 2.into(), 3.into(), 4.into()];
+        map.set("nums".parse().unwrap(), Value::Seq(seq)).unwrap();
+
+        let tag: Tag = "nums[-1]".parse().unwrap();
+        assert_eq!(map.get(&tag).unwrap(), Value::from(4));
+
+        let tag: Tag = "nums[-3-]".parse().unwrap();
+        assert_eq!(
+            map.get(&tag).unwrap(),
+            Value::Seq(vec![2.into(), 3.into(), 4.into()])
+        );
+
+        let tag: Tag = "nums[-3--1]".parse().unwrap();
+        assert_eq!(
+            map.get(&tag).unwrap(),
+            Value::Seq(vec![2.into(), 3.into(), 4.into()])
+        );
+
+        let tag: Tag = "nums[-1]".parse().unwrap();
+        map.set(tag, Value::from(40)).unwrap();
+        let tag: Tag = "nums[-1]".parse().unwrap();
+        assert_eq!(map.get(&tag).unwrap(), Value::from(40));
+
+        // on a len-5 seq, `-1` resolves to 4 and `3` stays 3: from > to once
+        // resolved, even though the raw values (-1 <= 3) look fine
+        let tag: Tag = "nums[-1-3]".parse().unwrap();
+        assert!(map.get(&tag).is_err());
+        assert!(map
+            .set(tag, Value::Seq(vec![Value::Unit]))
+            .is_err());
     }
 }